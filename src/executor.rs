@@ -0,0 +1,17 @@
+use std::future::Future;
+use std::pin::Pin;
+
+/// 一个生命周期为`'a`、可在线程间传递的装箱`Future`；`Scope::resource`
+/// /`Scope::effect_async`以`BoxFuture<'static, _>`的形式把异步任务交给注入的执行器驱动。
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output=T> + Send + 'a>>;
+
+/// 由调用方注入的异步任务执行器。`reactivity`本身不内置任何运行时（tokio/async-std等），
+/// 而是把"如何驱动一个被唤醒的`Future`继续执行"这件事委托给这个trait的实现者。
+///
+/// 重入风险：如果`spawn`同步/内联地驱动传入的`fut`（而不是把它放进队列异步执行），
+/// 且被驱动的任务又同步地触发了新一轮`wake`，`spawn`可能会在`wake`尚未返回时被重入。
+/// `reactivity`内部对此是安全的，但自定义`Spawner`实现如果也依赖某种独占状态，需要自行
+/// 考虑这种同步重入的情况。
+pub trait Spawner: Send + Sync {
+    fn spawn(&self, fut: BoxFuture<'static, ()>);
+}
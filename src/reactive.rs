@@ -55,6 +55,11 @@ impl<T: Clone> Reactive<T> {
     pub fn value(&self) -> T {
         self.inner.read().unwrap().value()
     }
+
+    /// 与`value`相同，但不会触发`getter_observers`，因此不会被当前正在追踪的`Effect`订阅。
+    pub fn value_untracked(&self) -> T {
+        self.inner.read().unwrap().value.clone()
+    }
 }
 
 impl<T: PartialEq> Reactive<T> {
@@ -141,6 +146,18 @@ mod test {
         assert_eq!(r.value(), 1);
     }
 
+    #[test]
+    fn test_value_untracked() {
+        let r = Reactive::new(0);
+        let count = Arc::new(RwLock::new(0));
+        let count2 = count.clone();
+        r.add_observer(move |_| *count2.write().unwrap() += 1, |_, _| {});
+        r.value_untracked();
+        assert_eq!(*count.read().unwrap(), 0);
+        r.value();
+        assert_eq!(*count.read().unwrap(), 1);
+    }
+
     #[test]
     fn test_reactive_vec() {
         let r = Reactive::new(vec![0]);
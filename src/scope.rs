@@ -1,13 +1,33 @@
-use std::collections::HashSet;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
+use std::future::Future;
 use std::hash::Hash;
-use std::sync::{Arc, RwLock};
-use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
 use weak_table::WeakKeyHashMap;
+use crate::executor::{BoxFuture, Spawner};
 use crate::reactive::{Reactive, ReactiveId, WeakReactiveId};
 
+thread_local! {
+    //为true时，create_get_listen不会把当前正在追踪的Effect记录进依赖map，用于实现untrack
+    static TRACKING_DISABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+struct TrackingGuard(bool);
+
+impl Drop for TrackingGuard {
+    fn drop(&mut self) {
+        TRACKING_DISABLED.with(|disabled| disabled.set(self.0));
+    }
+}
+
 type DynFnType = Arc<dyn Fn() + Send + Sync>;
 
+//按Effect::id索引的cleanup回调
+type CleanupMap = HashMap<u64, Vec<Box<dyn FnOnce() + Send>>>;
+
 static EFFECT_ID: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Clone)]
@@ -57,6 +77,16 @@ pub struct Scope {
     parent: Option<Arc<Scope>>,
     functions: Arc<RwLock<Vec<Effect>>>,
     map: Arc<RwLock<WeakKeyHashMap<WeakReactiveId, HashSet<Effect>>>>,
+    //不为0时表示当前处于batch中，触发的effect会被收集进pending_effects而不是立即执行
+    batch_depth: Arc<RwLock<u32>>,
+    pending_effects: Arc<RwLock<HashSet<Effect>>>,
+    //按Effect::id索引的cleanup回调，effect重新运行前或scope被dispose时执行
+    cleanups: Arc<Mutex<CleanupMap>>,
+    //指向通过create_child创建的子scope的弱引用，用于dispose时递归清理仍然存活的子scope
+    children: Arc<RwLock<Vec<Weak<Scope>>>>,
+    //由set_spawner注入，resource/effect_async用它来驱动首次poll之后剩余的异步工作；
+    //未设置时沿parent链向上查找
+    spawner: Arc<RwLock<Option<Arc<dyn Spawner>>>>,
     id: u64,
 }
 
@@ -84,20 +114,33 @@ impl Scope {
                 parent: None,
                 functions: Arc::new(RwLock::new(Vec::new())),
                 map: Arc::new(RwLock::new(WeakKeyHashMap::new())),
+                batch_depth: Arc::new(RwLock::new(0)),
+                pending_effects: Arc::new(RwLock::new(HashSet::new())),
+                cleanups: Arc::new(Mutex::new(HashMap::new())),
+                children: Arc::new(RwLock::new(Vec::new())),
+                spawner: Arc::new(RwLock::new(None)),
                 id: ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst),
             }
         )
     }
 
     pub fn create_child(self: &Arc<Self>) -> Arc<Self> {
-        Arc::new(
+        let child = Arc::new(
             Self {
                 parent: Some(self.clone()),
                 functions: Arc::new(RwLock::new(Vec::new())),
                 map: Arc::new(RwLock::new(WeakKeyHashMap::new())),
+                batch_depth: Arc::new(RwLock::new(0)),
+                pending_effects: Arc::new(RwLock::new(HashSet::new())),
+                cleanups: Arc::new(Mutex::new(HashMap::new())),
+                children: Arc::new(RwLock::new(Vec::new())),
+                spawner: Arc::new(RwLock::new(None)),
                 id: ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst),
             }
-        )
+        );
+        self.children.write().unwrap().push(Arc::downgrade(&child));
+
+        child
     }
 
     pub fn get_parent(self: &Arc<Self>) -> Option<Arc<Self>> {
@@ -111,6 +154,9 @@ impl Scope {
         let map = self.map.clone();
         let functions = self.functions.clone();
         let get_listen = move |_: &T| {
+            if TRACKING_DISABLED.with(|disabled| disabled.get()) {
+                return;
+            }
             if let Some(effect) = functions.read().unwrap().last() {
                 if !map.read().unwrap().contains_key(&id) {
                     map.write().unwrap().insert(id.clone(), HashSet::new());
@@ -122,11 +168,41 @@ impl Scope {
         get_listen
     }
 
+    //每次重新运行前，先执行该effect上一次遗留的cleanup回调，再把它从所有依赖集合中移除重新追踪依赖，
+    //这样它不再读取的信号会被自然遗忘，条件分支读取的信号也能被正确捕获
+    fn run_effect(map: &Arc<RwLock<WeakKeyHashMap<WeakReactiveId, HashSet<Effect>>>>, functions: &Arc<RwLock<Vec<Effect>>>, cleanups: &Arc<Mutex<CleanupMap>>, effect: &Effect) {
+        if let Some(fns) = cleanups.lock().unwrap().remove(&effect.id) {
+            for f in fns {
+                f();
+            }
+        }
+
+        for set in map.write().unwrap().values_mut() {
+            set.remove(effect);
+        }
+        functions.write().unwrap().push(effect.clone());
+        effect.call();
+        functions.write().unwrap().pop();
+    }
+
     fn create_set_listen<T>(self: &mut Arc<Self>, id: ReactiveId) -> impl Fn(&T, &T) + Send + 'static {
         let map = self.map.clone();
+        let functions = self.functions.clone();
+        let batch_depth = self.batch_depth.clone();
+        let pending_effects = self.pending_effects.clone();
+        let cleanups = self.cleanups.clone();
         let set_listen = move |_: &T, _: &T| {
-            if let Some(effects) = map.read().unwrap().get(&id) {
-                effects.iter().for_each(|effect| effect.call());
+            let effects = map.read().unwrap().get(&id).cloned();
+            if let Some(effects) = effects {
+                let in_batch = *batch_depth.read().unwrap() > 0;
+                for effect in effects {
+                    if in_batch {
+                        //batch激活时，把effect收集进pending_effects去重，等最外层batch结束时再统一执行
+                        pending_effects.write().unwrap().insert(effect);
+                    } else {
+                        Self::run_effect(&map, &functions, &cleanups, &effect);
+                    }
+                }
             }
         };
 
@@ -149,11 +225,212 @@ impl Scope {
         effect.call();
         self.functions.write().unwrap().pop();
     }
+
+    /// 创建一个带缓存的派生信号：`f`在依赖的`Reactive`发生变化时才会重新执行（变化时立即重新计算，
+    /// 而不是推迟到下一次读取），
+    /// 并通过`Reactive::update`写回结果，因此当结果值未变化时不会触发下游的`setter_observers`。
+    /// 返回的`Reactive<T>`应当被当作只读值使用，它的值只应由`f`驱动。
+    pub fn memo<T: PartialEq + Clone + Send + Sync + 'static>(self: &mut Arc<Self>, f: impl Fn() -> T + Send + Sync + 'static) -> Reactive<T> {
+        //种子值的计算发生在memo自己的effect之外，若调用memo时外层正有effect在运行，
+        //这次读取不应该被错误地记进外层effect的依赖
+        let seed = self.untrack(|| f());
+        let backing = self.reactive(seed);
+        let setter = backing.clone();
+        self.effect(move || {
+            let value = f();
+            setter.update(|_| value.clone());
+        });
+
+        backing
+    }
+
+    /// 在`f`执行期间暂停依赖追踪：`f`内部对`Reactive::value()`的调用不会让当前正在运行的
+    /// `Effect`订阅这些信号。适合在effect内部读取一些配置性质的信号，而不希望它们的变化
+    /// 触发该effect重新运行。
+    pub fn untrack<R>(self: &Arc<Self>, f: impl FnOnce() -> R) -> R {
+        let prev = TRACKING_DISABLED.with(|disabled| disabled.replace(true));
+        let _guard = TrackingGuard(prev);
+        f()
+    }
+
+    /// 在`f`执行期间，信号更新触发的effect不会立即重新运行，而是去重后收集起来，
+    /// 等最外层的batch结束时统一执行一次，从而把多次信号更新合并成一次原子性的重新计算。
+    /// 支持嵌套调用：只有最外层的batch结束时才会flush。
+    pub fn batch<R>(self: &mut Arc<Self>, f: impl FnOnce() -> R) -> R {
+        *self.batch_depth.write().unwrap() += 1;
+
+        let result = f();
+
+        let depth = {
+            let mut depth = self.batch_depth.write().unwrap();
+            *depth -= 1;
+            *depth
+        };
+
+        if depth == 0 {
+            let pending: Vec<Effect> = self.pending_effects.write().unwrap().drain().collect();
+            for effect in pending {
+                Self::run_effect(&self.map, &self.functions, &self.cleanups, &effect);
+            }
+        }
+
+        result
+    }
+
+    /// 注册一个与当前正在运行的effect关联的cleanup回调：该回调会在这个effect下一次重新运行前
+    /// 立即执行；若在此之前scope被`dispose`，则在dispose时执行。每个回调只执行一次。用于在effect重新运行或scope销毁时
+    /// 释放定时器、取消订阅等副作用。若当前没有正在运行的effect（即不在effect内部调用），则什么都不做。
+    pub fn on_cleanup(self: &Arc<Self>, f: impl FnOnce() + Send + 'static) {
+        if let Some(effect) = self.functions.read().unwrap().last() {
+            self.cleanups.lock().unwrap().entry(effect.id).or_default().push(Box::new(f));
+        }
+    }
+
+    /// 销毁这个scope：递归dispose仍然存活的子scope，执行所有遗留的cleanup回调，
+    /// 并清空自身的依赖`map`和`functions`栈。
+    pub fn dispose(self: &Arc<Self>) {
+        for child in self.children.write().unwrap().drain(..) {
+            if let Some(child) = child.upgrade() {
+                child.dispose();
+            }
+        }
+
+        let cleanups: Vec<Box<dyn FnOnce() + Send>> = self.cleanups.lock().unwrap().drain().flat_map(|(_, fns)| fns).collect();
+        for f in cleanups {
+            f();
+        }
+
+        self.functions.write().unwrap().clear();
+        self.map.write().unwrap().clear();
+    }
+
+    /// 为这个scope注入一个`Spawner`，`resource`/`effect_async`会用它来驱动首次poll之后
+    /// 剩余的异步工作。未显式设置时，子scope会沿着`parent`链向上查找。
+    pub fn set_spawner(self: &Arc<Self>, spawner: Arc<dyn Spawner>) {
+        *self.spawner.write().unwrap() = Some(spawner);
+    }
+
+    fn resolve_spawner(self: &Arc<Self>) -> Option<Arc<dyn Spawner>> {
+        if let Some(spawner) = self.spawner.read().unwrap().clone() {
+            return Some(spawner);
+        }
+        self.parent.as_ref().and_then(|parent| parent.resolve_spawner())
+    }
+
+    /// 创建一个由异步任务驱动的只读信号：每次依赖的`Reactive`变化时，`f`会被重新调用一次，
+    /// 产生的`Future`先被同步poll一次——这一次poll发生在effect的追踪窗口内，所以`f`在第一个
+    /// `.await`之前读取的信号会被正常记录为依赖；如果第一次poll没有立刻就绪，剩余的poll交给
+    /// 注入的`Spawner`驱动完成，完成后把结果写回返回的`Reactive`。这些后续poll不会关闭依赖追踪：
+    /// 若`Spawner`在`spawn`内同步地驱动任务，或在某个effect运行期间驱动任务，此时读取的信号
+    /// 仍会被当时正在运行的effect记录为依赖。
+    /// 如果在一次异步任务完成之前又有新的触发，旧的那次运行会被新的取代，不会再写回结果。
+    /// 每次重新运行开始时，返回的`Reactive`会先被重置为`None`，代表进入loading状态，
+    /// 直到这次运行真正resolve才写回`Some(value)`。
+    pub fn resource<T: PartialEq + Clone + Send + Sync + 'static>(self: &mut Arc<Self>, f: impl Fn() -> BoxFuture<'static, T> + Send + Sync + 'static) -> Reactive<Option<T>> {
+        let backing = self.reactive(None::<T>);
+        let generation = Arc::new(RwLock::new(0u64));
+        let spawner = self.resolve_spawner().expect("Scope::resource requires a Spawner; call Scope::set_spawner first");
+
+        let backing2 = backing.clone();
+        self.effect(move || {
+            let run_id = {
+                let mut guard = generation.write().unwrap();
+                *guard += 1;
+                *guard
+            };
+
+            //重新进入loading状态，让下游能感知到一次新的运行正在进行中
+            backing2.update(|_| None);
+
+            let driver = Arc::new(ResourceDriver {
+                fut: Mutex::new(Some(f())),
+                run_id,
+                generation: generation.clone(),
+                backing: backing2.clone(),
+                spawner: spawner.clone(),
+                woken_while_polling: AtomicBool::new(false),
+            });
+            driver.poll_once();
+        });
+
+        backing
+    }
+
+    /// 创建一个异步effect：与`effect`类似，但`f`返回一个`Future`，其中第一个`.await`之前的
+    /// 同步部分会像普通effect一样追踪依赖，之后的部分通过注入的`Spawner`异步驱动完成。
+    pub fn effect_async<Fut: Future<Output=()> + Send + 'static>(self: &mut Arc<Self>, f: impl Fn() -> Fut + Send + Sync + 'static) {
+        self.resource(move || Box::pin(f()) as BoxFuture<'static, ()>);
+    }
+}
+
+//驱动单次resource运行产生的Future：持有该次运行的generation，只有当它仍然是最新一次运行时，
+//poll得到的结果才会被写回backing；一旦被更新的触发取代，结果会被静默丢弃，从而保证不会乱序写入。
+struct ResourceDriver<T> {
+    fut: Mutex<Option<BoxFuture<'static, T>>>,
+    run_id: u64,
+    generation: Arc<RwLock<u64>>,
+    backing: Reactive<Option<T>>,
+    spawner: Arc<dyn Spawner>,
+    //若一次poll()内部同步地唤醒了自己（见Spawner trait上的说明），重入的poll_once会发现
+    //fut已经被取走而无法立即重新poll，于是在这里记一笔，等外层poll返回后补上这次poll
+    woken_while_polling: AtomicBool,
+}
+
+impl<T: PartialEq + Clone + Send + Sync + 'static> ResourceDriver<T> {
+    fn is_current(&self) -> bool {
+        *self.generation.read().unwrap() == self.run_id
+    }
+
+    fn poll_once(self: &Arc<Self>) {
+        if !self.is_current() {
+            *self.fut.lock().unwrap() = None;
+            return;
+        }
+
+        //先把fut从锁里取出来，再在不持有锁的情况下poll它：如果poll内部同步调用了wake，
+        //spawner又是同步/内联执行的，Wake::wake会重入poll_once，此时锁必须是空闲的，否则会自锁死
+        let Some(mut fut) = self.fut.lock().unwrap().take() else {
+            self.woken_while_polling.store(true, Ordering::SeqCst);
+            return;
+        };
+
+        loop {
+            self.woken_while_polling.store(false, Ordering::SeqCst);
+            let waker = Waker::from(self.clone());
+            let mut cx = Context::from_waker(&waker);
+            match fut.as_mut().poll(&mut cx) {
+                Poll::Pending => {
+                    //poll期间发生了一次同步的自我唤醒：那次重入什么都没做，这里立刻重新poll一次来补上
+                    if self.woken_while_polling.swap(false, Ordering::SeqCst) {
+                        continue;
+                    }
+                    *self.fut.lock().unwrap() = Some(fut);
+                    return;
+                }
+                Poll::Ready(value) => {
+                    if self.is_current() {
+                        self.backing.update(move |_| Some(value.clone()));
+                    }
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl<T: PartialEq + Clone + Send + Sync + 'static> Wake for ResourceDriver<T> {
+    fn wake(self: Arc<Self>) {
+        let this = self.clone();
+        self.spawner.spawn(Box::pin(async move {
+            this.poll_once();
+        }));
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::pin::Pin;
 
     #[test]
     fn test_get_parent() {
@@ -183,4 +460,313 @@ mod test {
         r.update(|count| count + 1);
         assert_eq!(r.value(), 1);
     }
+
+    #[test]
+    fn test_memo() {
+        let mut scope = Scope::new();
+        let r = scope.reactive(1);
+        let r2 = r.clone();
+        let double = scope.memo(move || r2.value() * 2);
+        assert_eq!(double.value(), 2);
+        r.update(|count| count + 1);
+        assert_eq!(double.value(), 4);
+    }
+
+    #[test]
+    fn test_conditional_dependency_retracked() {
+        let mut scope = Scope::new();
+        let a = scope.reactive(true);
+        let b = scope.reactive(0);
+        let runs = scope.reactive(0);
+
+        let a2 = a.clone();
+        let b2 = b.clone();
+        let runs2 = runs.clone();
+        scope.effect(move || {
+            if a2.value() {
+                b2.value();
+            }
+            runs2.update(|count| count + 1);
+        });
+        assert_eq!(runs.value(), 1);
+
+        //b未被读取时改变b不应触发effect重新运行
+        a.update(|_| false);
+        assert_eq!(runs.value(), 2);
+        b.update(|count| count + 1);
+        assert_eq!(runs.value(), 2);
+
+        //重新开启对b的追踪后，改变b应再次触发effect
+        a.update(|_| true);
+        assert_eq!(runs.value(), 3);
+        b.update(|count| count + 1);
+        assert_eq!(runs.value(), 4);
+    }
+
+    #[test]
+    fn test_untrack() {
+        let mut scope = Scope::new();
+        let config = scope.reactive(0);
+        let runs = scope.reactive(0);
+
+        let config2 = config.clone();
+        let runs2 = runs.clone();
+        let scope2 = scope.clone();
+        scope.effect(move || {
+            scope2.untrack(|| {
+                config2.value();
+            });
+            runs2.update(|count| count + 1);
+        });
+        assert_eq!(runs.value(), 1);
+
+        //untrack内部读取的config不应该被effect订阅
+        config.update(|count| count + 1);
+        assert_eq!(runs.value(), 1);
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut scope = Scope::new();
+        let a = scope.reactive(0);
+        let b = scope.reactive(0);
+        let runs = scope.reactive(0);
+
+        let a2 = a.clone();
+        let b2 = b.clone();
+        let runs2 = runs.clone();
+        scope.effect(move || {
+            a2.value();
+            b2.value();
+            runs2.update(|count| count + 1);
+        });
+        assert_eq!(runs.value(), 1);
+
+        let a3 = a.clone();
+        let b3 = b.clone();
+        let runs3 = runs.clone();
+        let mut scope2 = scope.clone();
+        scope.batch(move || {
+            a3.update(|count| count + 1);
+            b3.update(|count| count + 1);
+            //batch内部effect不应立即重新运行
+            assert_eq!(runs3.value(), 1);
+            //嵌套batch不应提前flush
+            let a4 = a3.clone();
+            scope2.batch(move || {
+                a4.update(|count| count + 1);
+            });
+            assert_eq!(runs3.value(), 1);
+        });
+
+        //batch结束后effect只应执行一次
+        assert_eq!(runs.value(), 2);
+    }
+
+    #[test]
+    fn test_on_cleanup_runs_before_rerun() {
+        let mut scope = Scope::new();
+        let a = scope.reactive(0);
+        let cleanup_count = scope.reactive(0);
+
+        let a2 = a.clone();
+        let cleanup_count2 = cleanup_count.clone();
+        let scope2 = scope.clone();
+        scope.effect(move || {
+            a2.value();
+            let cleanup_count3 = cleanup_count2.clone();
+            scope2.on_cleanup(move || {
+                cleanup_count3.update(|count| count + 1);
+            });
+        });
+        assert_eq!(cleanup_count.value(), 0);
+
+        a.update(|count| count + 1);
+        assert_eq!(cleanup_count.value(), 1);
+
+        a.update(|count| count + 1);
+        assert_eq!(cleanup_count.value(), 2);
+    }
+
+    #[test]
+    fn test_dispose_runs_cleanups_and_clears_scope() {
+        let scope = Scope::new();
+        let mut scope2 = scope.clone();
+        let scope3 = scope.clone();
+        let a = scope2.reactive(0);
+        let disposed = scope2.reactive(false);
+
+        let disposed2 = disposed.clone();
+        scope2.effect(move || {
+            a.value();
+            let disposed3 = disposed2.clone();
+            scope3.on_cleanup(move || {
+                disposed3.update(|_| true);
+            });
+        });
+        assert!(!disposed.value());
+
+        scope.dispose();
+        assert!(disposed.value());
+        assert_eq!(scope.map.read().unwrap().len(), 0);
+        assert_eq!(scope.functions.read().unwrap().len(), 0);
+    }
+
+    //只在首次poll时返回Pending（并立刻唤醒自己），第二次poll才真正完成，
+    //用它在没有真实异步运行时的情况下模拟一个跨越了一次`.await`的Future
+    struct YieldOnce<T> {
+        yielded: bool,
+        value: Option<T>,
+    }
+
+    impl<T: Unpin> Future for YieldOnce<T> {
+        type Output = T;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+            if !self.yielded {
+                self.yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            } else {
+                Poll::Ready(self.value.take().unwrap())
+            }
+        }
+    }
+
+    struct NoopWake;
+
+    impl Wake for NoopWake {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    //测试专用的同步执行器：把spawn的任务攒起来，run_until_idle时反复poll直到没有任务处于pending，
+    //刻意以倒序处理同一批任务，模拟真实执行器里任务完成顺序可能与提交顺序不同的情况
+    struct ImmediateSpawner {
+        pending: Mutex<Vec<BoxFuture<'static, ()>>>,
+    }
+
+    impl ImmediateSpawner {
+        fn new() -> Arc<Self> {
+            Arc::new(Self { pending: Mutex::new(Vec::new()) })
+        }
+
+        fn run_until_idle(&self) {
+            let waker = Waker::from(Arc::new(NoopWake));
+            let mut cx = Context::from_waker(&waker);
+            loop {
+                let tasks: Vec<_> = self.pending.lock().unwrap().drain(..).rev().collect();
+                if tasks.is_empty() {
+                    break;
+                }
+                for mut task in tasks {
+                    let _ = task.as_mut().poll(&mut cx);
+                }
+            }
+        }
+    }
+
+    impl Spawner for ImmediateSpawner {
+        fn spawn(&self, fut: BoxFuture<'static, ()>) {
+            self.pending.lock().unwrap().push(fut);
+        }
+    }
+
+    #[test]
+    fn test_resource_tracks_pre_await_reads_and_resumes_via_spawner() {
+        let mut scope = Scope::new();
+        let spawner = ImmediateSpawner::new();
+        scope.set_spawner(spawner.clone());
+
+        let trigger = scope.reactive(1);
+        let trigger2 = trigger.clone();
+        let resource = scope.resource(move || {
+            let n = trigger2.value();
+            Box::pin(YieldOnce { yielded: false, value: Some(n * 10) }) as BoxFuture<'static, i32>
+        });
+
+        //第一次poll只会跨过第一个yield点，此时异步工作还没完成
+        assert_eq!(resource.value(), None);
+        spawner.run_until_idle();
+        assert_eq!(resource.value(), Some(10));
+
+        //重新追踪后，改变trigger应该再次驱动resource重新运行
+        trigger.update(|n| n + 1);
+        assert_eq!(resource.value(), None);
+        spawner.run_until_idle();
+        assert_eq!(resource.value(), Some(20));
+    }
+
+    #[test]
+    fn test_resource_supersedes_stale_future() {
+        let mut scope = Scope::new();
+        let spawner = ImmediateSpawner::new();
+        scope.set_spawner(spawner.clone());
+
+        let trigger = scope.reactive(1);
+        let trigger2 = trigger.clone();
+        let resource = scope.resource(move || {
+            let n = trigger2.value();
+            Box::pin(YieldOnce { yielded: false, value: Some(n * 10) }) as BoxFuture<'static, i32>
+        });
+        assert_eq!(resource.value(), None);
+
+        //在第一次运行完成之前就触发第二次运行
+        trigger.update(|n| n + 1);
+        assert_eq!(resource.value(), None);
+
+        //即使执行器碰巧先让更新的那次运行完成、旧的那次运行随后才完成，结果也不应该被旧值覆盖
+        spawner.run_until_idle();
+        assert_eq!(resource.value(), Some(20));
+    }
+
+    //跨过一次yield点之后执行一个FnOnce，用来在effect_async测试里验证"异步完成之后产生副作用"
+    struct YieldThenRun<F: FnOnce() + Unpin> {
+        yielded: bool,
+        f: Option<F>,
+    }
+
+    impl<F: FnOnce() + Unpin> Future for YieldThenRun<F> {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if !self.yielded {
+                self.yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            } else {
+                if let Some(f) = self.f.take() {
+                    f();
+                }
+                Poll::Ready(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_effect_async_tracks_and_runs_to_completion() {
+        let mut scope = Scope::new();
+        let spawner = ImmediateSpawner::new();
+        scope.set_spawner(spawner.clone());
+
+        let trigger = scope.reactive(1);
+        let done = scope.reactive(0);
+
+        let trigger2 = trigger.clone();
+        let done2 = done.clone();
+        scope.effect_async(move || {
+            let n = trigger2.value();
+            let done3 = done2.clone();
+            YieldThenRun { yielded: false, f: Some(move || done3.update(|_| n)) }
+        });
+
+        assert_eq!(done.value(), 0);
+        spawner.run_until_idle();
+        assert_eq!(done.value(), 1);
+
+        //trigger在第一个yield点之前被读取，改变它应该让effect_async重新运行
+        trigger.update(|n| n + 1);
+        assert_eq!(done.value(), 1);
+        spawner.run_until_idle();
+        assert_eq!(done.value(), 2);
+    }
 }
\ No newline at end of file